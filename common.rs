@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::result::Result;
+
+/// Result type used throughout the indexer. Any error is boxed so that I/O, rss, url, and our own
+/// `RssIndexError` can all flow through the same `?`.
+pub type RssIndexResult<T> = Result<T, Box<dyn Error>>;
+
+/// Errors that originate in the indexer itself (as opposed to a library we call into).
+#[derive(Debug)]
+pub enum RssIndexError {
+    /// The command-line arguments didn't match a known mode.
+    ArgsError,
+    /// A feed or article was missing a url/hostname/title we needed.
+    UrlError,
+    /// Some jobs failed (e.g. a feed panicked) but others succeeded; each failure is reported.
+    PartialFailure(Vec<crate::threadpool::JobError>),
+}
+
+impl fmt::Display for RssIndexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RssIndexError::ArgsError => write!(f, "invalid arguments"),
+            RssIndexError::UrlError => write!(f, "missing or malformed url"),
+            RssIndexError::PartialFailure(errors) => {
+                write!(f, "{} job(s) failed:", errors.len())?;
+                for error in errors {
+                    write!(f, "\n  {}", error)?;
+                }
+                Result::Ok(())
+            }
+        }
+    }
+}
+
+impl Error for RssIndexError {}
+
+/// A single article, identified by the triple (site, title, url). Two articles compare equal iff
+/// all three match, so the same headline served from two hosts stays distinct.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Article {
+    pub site: String,
+    pub title: String,
+    pub url: String,
+}
+
+impl Article {
+    /// Build an article from its url and title, with an empty site. `ArticleIndex::add` takes the
+    /// site separately and builds its own (site, title, url) key.
+    pub fn new(url: String, title: String) -> Self {
+        Article {
+            site: String::new(),
+            title,
+            url,
+        }
+    }
+}
+
+/// The raw, per-article index produced while crawling: each article maps to the word-frequency
+/// counts gathered by `process_article`. `build_index` later inverts this into an `RssIndex`.
+pub struct ArticleIndex {
+    pub articles: HashMap<Article, HashMap<String, u32>>,
+}
+
+impl ArticleIndex {
+    pub fn new() -> Self {
+        ArticleIndex {
+            articles: HashMap::new(),
+        }
+    }
+
+    /// Record one article's word counts under the (site, title, url) key, summing counts if the
+    /// same article is added twice.
+    pub fn add(
+        &mut self,
+        site: String,
+        title: String,
+        url: String,
+        article_words: HashMap<String, u32>,
+    ) {
+        let article = Article { site, title, url };
+        let entry = self.articles.entry(article).or_insert_with(HashMap::new);
+        for (word, count) in article_words {
+            *entry.entry(word).or_insert(0) += count;
+        }
+    }
+
+    /// Fold another index into this one, summing word hit counts for any articles that share the
+    /// same (site, title, url). Used to reduce the per-feed partial indices built in parallel back
+    /// into a single index without a global lock on the hot path.
+    pub fn merge(&mut self, other: ArticleIndex) {
+        for (article, words) in other.articles {
+            let entry = self.articles.entry(article).or_insert_with(HashMap::new);
+            for (word, count) in words {
+                *entry.entry(word).or_insert(0) += count;
+            }
+        }
+    }
+}
+
+impl Default for ArticleIndex {
+    fn default() -> Self {
+        ArticleIndex::new()
+    }
+}
+
+/// The finished, searchable index: each word maps to the articles it appears in and how often.
+pub struct RssIndex {
+    pub index: HashMap<String, HashMap<Article, u32>>,
+}
+
+impl RssIndex {
+    pub fn new() -> Self {
+        RssIndex {
+            index: HashMap::new(),
+        }
+    }
+}
+
+impl Default for RssIndex {
+    fn default() -> Self {
+        RssIndex::new()
+    }
+}
+
+/// Invert an `ArticleIndex` (article -> word counts) into an `RssIndex` (word -> article hits).
+pub fn build_index(article_index: &mut ArticleIndex, rss_index: &mut RssIndex) {
+    for (article, words) in &article_index.articles {
+        for (word, count) in words {
+            rss_index
+                .index
+                .entry(word.clone())
+                .or_insert_with(HashMap::new)
+                .insert(article.clone(), *count);
+        }
+    }
+}
+
+/// Return the article's word-frequency counts. Nothing is fetched: the title is the only text we
+/// have without scraping each page, so we tokenize it into lowercased alphanumeric words.
+pub fn process_article(article: &Article) -> RssIndexResult<HashMap<String, u32>> {
+    let mut words = HashMap::new();
+    for token in article.title.split(|c: char| !c.is_alphanumeric()) {
+        if token.is_empty() {
+            continue;
+        }
+        *words.entry(token.to_lowercase()).or_insert(0) += 1;
+    }
+    Result::Ok(words)
+}
+
+/// Trait used to box a `FnOnce` closure so it can be sent to a worker thread and called later.
+/// (Calling a boxed `FnOnce` directly isn't possible on stable without this indirection.) Jobs are
+/// handed the worker's `WorkContext` so they can report progress as they run.
+pub trait FnBox {
+    fn call_box(self: Box<Self>, ctx: &crate::threadpool::WorkContext);
+}
+
+impl<F: FnOnce(&crate::threadpool::WorkContext)> FnBox for F {
+    fn call_box(self: Box<F>, ctx: &crate::threadpool::WorkContext) {
+        (*self)(ctx)
+    }
+}
@@ -3,11 +3,12 @@
 
 use std::env;
 use std::io;
-use std::sync::{Arc, Mutex};
 
 mod common;
+mod markov;
 mod multi;
 mod pooled;
+mod server;
 mod single;
 mod threadpool;
 
@@ -23,47 +24,104 @@ fn build_single(filename: &str) -> common::RssIndexResult<common::RssIndex> {
     Result::Ok(rss_index)
 }
 
-fn build_multi(filename: &str) -> common::RssIndexResult<common::RssIndex> {
-    let article_index = Arc::new(Mutex::new(common::ArticleIndex::new()));
+fn build_multi(
+    filename: &str,
+) -> common::RssIndexResult<(common::RssIndex, Vec<threadpool::JobError>)> {
+    let (mut article_index, errors) = multi::process_feed_file(filename)?;
     let mut rss_index = common::RssIndex::new();
 
-    multi::process_feed_file(filename, article_index.clone())?;
-
-    let mut final_index = article_index.lock().unwrap();
-
-    common::build_index(&mut final_index, &mut rss_index);
+    common::build_index(&mut article_index, &mut rss_index);
 
-    Result::Ok(rss_index)
+    Result::Ok((rss_index, errors))
 }
 
-fn build_pooled(filename: &str) -> common::RssIndexResult<common::RssIndex> {
-    let article_index = Arc::new(Mutex::new(common::ArticleIndex::new()));
+fn build_pooled(
+    filename: &str,
+) -> common::RssIndexResult<(common::RssIndex, markov::MarkovModel, Vec<threadpool::JobError>)> {
+    let (mut article_index, model, errors) = pooled::process_feed_file(filename)?;
     let mut rss_index = common::RssIndex::new();
 
-    pooled::process_feed_file(filename, article_index.clone())?;
+    common::build_index(&mut article_index, &mut rss_index);
 
-    let mut final_index = article_index.lock().unwrap();
+    Result::Ok((rss_index, model, errors))
+}
 
-    common::build_index(&mut final_index, &mut rss_index);
+/// Drop into a small prompt loop that turns seed prefixes into synthetic headlines sampled from
+/// the Markov model. An empty line leaves generation and returns to the search loop.
+fn generate_headlines(model: &markov::MarkovModel) -> common::RssIndexResult<()> {
+    const HEADLINES_PER_SEED: u64 = 3;
+    const MAX_WORDS: usize = 16;
 
-    Result::Ok(rss_index)
+    let mut buffer = String::new();
+    loop {
+        println!("Enter a seed prefix to generate headlines [or just hit <enter> to skip]: ");
+        buffer.clear();
+        io::stdin().read_line(&mut buffer)?;
+        let seed = markov::tokenize(buffer.trim());
+        if seed.is_empty() {
+            return Result::Ok(());
+        }
+        for n in 0..HEADLINES_PER_SEED {
+            // Give each headline its own seed so they can differ while staying reproducible. The
+            // offset is shifted past bit 0, which `SmallRng::new` always sets.
+            let mut rng = markov::SmallRng::new(0x9E37_79B9_7F4A_7C15 ^ ((n + 1) << 1));
+            let words = model.generate(&seed, MAX_WORDS, &mut rng);
+            println!("  {}", words.join(" "));
+        }
+    }
 }
 
 fn main() -> common::RssIndexResult<()> {
-    let mut args = env::args().skip(1);
-
-    let rss_index = match (args.next(), args.next().as_ref().map(String::as_str)) {
-        (Some(f), Some("single")) => build_single(&f)?,
-        (Some(f), Some("multi")) => build_multi(&f)?,
-        (Some(f), Some("pool")) => build_pooled(&f)?,
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mode = args.get(1).map(String::as_str);
+    let generate = args.iter().skip(2).any(|a| a == "--generate");
+    let serve_addr = args
+        .iter()
+        .position(|a| a == "--serve")
+        .and_then(|i| args.get(i + 1).cloned());
+
+    // Only the pool path builds a Markov model; the other modes leave it `None`. Each path also
+    // yields the feeds/articles that failed, so we can exit non-zero once the session ends.
+    let (rss_index, model, build_errors) = match (args.first(), mode) {
+        (Some(f), Some("single")) => (build_single(f)?, None, vec![]),
+        (Some(f), Some("multi")) => {
+            let (index, errors) = build_multi(f)?;
+            (index, None, errors)
+        }
+        (Some(f), Some("pool")) => {
+            let (index, model, errors) = build_pooled(f)?;
+            (index, Some(model), errors)
+        }
         _ => {
-            println!("Usage: cargo run <filename.xml> [single|multi|pool]");
+            println!(
+                "Usage: cargo run <filename.xml> [single|multi|pool] [--generate] [--serve <addr>]"
+            );
             return Result::Err(Box::new(common::RssIndexError::ArgsError));
         }
     };
 
     println!("Done building index.");
 
+    // Warn up front about any feeds/articles that failed, keeping the index we did build.
+    if !build_errors.is_empty() {
+        eprintln!("{} feed/article(s) failed:", build_errors.len());
+        for error in &build_errors {
+            eprintln!("  {}", error);
+        }
+    }
+
+    // Serve mode replaces the stdin search loop with a long-running HTTP service.
+    if let Some(addr) = serve_addr {
+        return server::serve(&addr, rss_index);
+    }
+
+    if generate {
+        match model {
+            Some(ref model) => generate_headlines(model)?,
+            None => println!("--generate is only available in pool mode; skipping."),
+        }
+    }
+
     let mut buffer = String::new();
     let mut lower_buffer;
 
@@ -73,7 +131,7 @@ fn main() -> common::RssIndexResult<()> {
         io::stdin().read_line(&mut buffer)?;
         buffer = buffer.trim().to_string();
         if buffer.is_empty() {
-            return Result::Ok(());
+            break;
         }
         lower_buffer = buffer.to_lowercase();
         let matches = rss_index.index.get(&lower_buffer);
@@ -102,4 +160,11 @@ fn main() -> common::RssIndexResult<()> {
             }
         }
     }
+
+    // Exit non-zero if any feed/article failed during the build, so scripts can detect it.
+    if build_errors.is_empty() {
+        Result::Ok(())
+    } else {
+        Result::Err(Box::new(common::RssIndexError::PartialFailure(build_errors)))
+    }
 }
@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+/// Sentinels marking the start and end of a title. Control characters so they can't collide with
+/// a real word from a headline.
+const START: &str = "\u{2}";
+const END: &str = "\u{3}";
+
+/// A tiny deterministic xorshift generator, used to sample successor words. We avoid pulling in an
+/// external rng crate; reproducibility across runs is a feature here, not a bug.
+pub struct SmallRng {
+    state: u64,
+}
+
+impl SmallRng {
+    pub fn new(seed: u64) -> Self {
+        // Seed 0 is a fixed point of xorshift, so nudge it away from zero.
+        SmallRng {
+            state: seed | 1,
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.state = x;
+        x
+    }
+
+    /// A value in `0..bound` (bound must be non-zero).
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+}
+
+/// An order-N Markov model over article titles: each N-word prefix maps to the words that followed
+/// it and how often. Used to generate synthetic headlines or suggest search terms.
+pub struct MarkovModel {
+    order: usize,
+    transitions: HashMap<Vec<String>, HashMap<String, u32>>,
+}
+
+impl MarkovModel {
+    pub fn new(order: usize) -> Self {
+        MarkovModel {
+            order,
+            transitions: HashMap::new(),
+        }
+    }
+
+    /// Slide an N-gram window across `words` (padded with start/end sentinels) and increment the
+    /// successor count for each prefix.
+    pub fn feed(&mut self, words: &[String]) {
+        if words.is_empty() {
+            return;
+        }
+        let mut sequence = vec![START.to_string(); self.order];
+        sequence.extend(words.iter().cloned());
+        sequence.push(END.to_string());
+        for window in sequence.windows(self.order + 1) {
+            let prefix = window[..self.order].to_vec();
+            let next = window[self.order].clone();
+            *self
+                .transitions
+                .entry(prefix)
+                .or_insert_with(HashMap::new)
+                .entry(next)
+                .or_insert(0) += 1;
+        }
+    }
+
+    /// Fold another model into this one, summing successor counts. Partial models built per feed
+    /// are reduced this way, mirroring `ArticleIndex::merge`.
+    pub fn merge(&mut self, other: MarkovModel) {
+        for (prefix, successors) in other.transitions {
+            let entry = self.transitions.entry(prefix).or_insert_with(HashMap::new);
+            for (word, count) in successors {
+                *entry.entry(word).or_insert(0) += count;
+            }
+        }
+    }
+
+    /// Sample one successor for `prefix`, weighted by count. Returns `None` if the prefix is
+    /// unknown (a dead end).
+    fn sample(&self, prefix: &[String], rng: &mut SmallRng) -> Option<String> {
+        let successors = self.transitions.get(prefix)?;
+        let total: u64 = successors.values().map(|c| *c as u64).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut target = rng.below(total);
+        for (word, count) in successors {
+            let count = *count as u64;
+            if target < count {
+                return Some(word.clone());
+            }
+            target -= count;
+        }
+        None
+    }
+
+    /// Generate a headline. If `seed` is empty, start from a random known start state weighted by
+    /// frequency; otherwise begin from the given prefix. Sampling stops at the end sentinel or once
+    /// `max_words` have been produced.
+    pub fn generate(&self, seed: &[String], max_words: usize, rng: &mut SmallRng) -> Vec<String> {
+        if self.order == 0 {
+            return vec![];
+        }
+        // Build the initial window: sentinel padding followed by the seed, keeping the last
+        // `order` tokens.
+        let mut window = vec![START.to_string(); self.order];
+        for word in seed {
+            window.remove(0);
+            window.push(word.clone());
+        }
+        let mut output: Vec<String> = seed.to_vec();
+        while output.len() < max_words {
+            match self.sample(&window, rng) {
+                Some(ref word) if word == END => break,
+                Some(word) => {
+                    output.push(word.clone());
+                    window.remove(0);
+                    window.push(word);
+                }
+                None => break,
+            }
+        }
+        output
+    }
+}
+
+/// Split a title into the word tokens the model is fed and generates. Lowercased alphanumeric runs,
+/// matching how `process_article` tokenizes text.
+pub fn tokenize(title: &str) -> Vec<String> {
+    title
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(|t| t.to_lowercase())
+        .collect()
+}
@@ -4,11 +4,12 @@ use std::fs::File;
 use std::io::BufReader;
 use std::result::Result;
 
-use std::sync::{Arc, Condvar, Mutex};
+use std::sync::{mpsc, Arc, Condvar, Mutex};
 use std::thread;
 use url::Url;
 
 use crate::common::*;
+use crate::threadpool::{panic_message, JobError};
 
 /// Thread limits.
 const MAX_THREADS_FEEDS: u32 = 5;
@@ -38,8 +39,11 @@ pub struct ThreadCount {
 }
 
 /// Same as for the single-threaded version, but now spawn a new thread for each call to
-/// `process_feed`. Make sure to respect the thread limits!
-pub fn process_feed_file(file_name: &str, index: Arc<Mutex<ArticleIndex>>) -> RssIndexResult<()> {
+/// `process_feed`. Make sure to respect the thread limits! Each feed thread builds its own partial
+/// `ArticleIndex` and ships it back over a channel; the main thread reduces the partials into one.
+/// Any feeds or articles that failed are returned alongside the merged index so the caller can
+/// signal them without discarding everything that indexed cleanly.
+pub fn process_feed_file(file_name: &str) -> RssIndexResult<(ArticleIndex, Vec<JobError>)> {
     let thread_count = Arc::new(ThreadCount {
         feeds_count: CvarLock::new(0),
         sites_count: CvarLock::new(HashMap::new()), //default u32 = 0
@@ -49,8 +53,14 @@ pub fn process_feed_file(file_name: &str, index: Arc<Mutex<ArticleIndex>>) -> Rs
     let file = File::open(file_name)?;
     println!("Processing feed file: {}", file_name);
     let channel = Channel::read_from(BufReader::new(file))?;
+    // Each feed ships back its partial index together with any per-article failures it caught, so
+    // one bad article no longer vanishes silently.
+    let (partials_tx, partials_rx) = mpsc::channel::<(ArticleIndex, Vec<JobError>)>();
     for feed in channel.into_items() {
-        let ind = Arc::clone(&index);
+        // Label the feed for error reporting before it is moved into the thread.
+        let feed_label = feed.link().unwrap_or_default().to_string();
+        let ind = Arc::new(Mutex::new(ArticleIndex::new()));
+        let tx = partials_tx.clone();
         let tc = Arc::clone(&thread_count);
         let handle = thread::spawn(move || {
             let fc_cvl = &tc.feeds_count;
@@ -79,7 +89,22 @@ pub fn process_feed_file(file_name: &str, index: Arc<Mutex<ArticleIndex>>) -> Rs
             let url = feed.link().ok_or(RssIndexError::UrlError).unwrap();
             let title = feed.title().ok_or(RssIndexError::UrlError).unwrap();
             println!("Processing feed: {} [{}]", title, url);
-            process_feed(url, ind, Arc::clone(&tc)).unwrap();
+            // A failure setting up the feed (e.g. a dead url) becomes a single feed-level error
+            // rather than panicking the thread and losing every other feed.
+            let errors = match process_feed(url, Arc::clone(&ind), Arc::clone(&tc)) {
+                Result::Ok(errors) => errors,
+                Result::Err(error) => vec![JobError {
+                    url: url.to_string(),
+                    payload: error.to_string(),
+                }],
+            };
+            // This feed's article threads have all joined; ship its partial index to the reducer.
+            let partial = Arc::try_unwrap(ind)
+                .ok()
+                .expect("article threads outlived their feed")
+                .into_inner()
+                .unwrap();
+            tx.send((partial, errors)).unwrap();
             {
                 let mut fc = fc_lock.lock().unwrap();
                 *fc -= 1; //decrement feeds_count
@@ -93,12 +118,29 @@ pub fn process_feed_file(file_name: &str, index: Arc<Mutex<ArticleIndex>>) -> Rs
                 tc_cvar.notify_one();
             }
         });
-        handles.push(handle);
+        handles.push((feed_label, handle));
+    }
+    // Drop our own sender so the reduce loop terminates once every feed thread has reported.
+    drop(partials_tx);
+    // Join the feed threads, turning any that panicked outright into a feed-level error.
+    let mut errors: Vec<JobError> = vec![];
+    for (label, handle) in handles {
+        if let Result::Err(payload) = handle.join() {
+            errors.push(JobError {
+                url: label,
+                payload: panic_message(payload),
+            });
+        }
     }
-    for handle in handles {
-        handle.join();
+    // Reduce: fold every feed's partial index into the result, gathering its article failures.
+    let mut index = ArticleIndex::new();
+    for (partial, mut feed_errors) in partials_rx {
+        index.merge(partial);
+        errors.append(&mut feed_errors);
     }
-    Result::Ok(())
+    // Hand the failures back with the merged index: one bad feed shouldn't throw away everything
+    // that indexed cleanly, but the caller still gets to surface them.
+    Result::Ok((index, errors))
 }
 
 /// Same as for the single-threaded version, but now spawn a new thread for each call to
@@ -107,7 +149,7 @@ fn process_feed(
     url: &str,
     index: Arc<Mutex<ArticleIndex>>,
     counters: Arc<ThreadCount>,
-) -> RssIndexResult<()> {
+) -> RssIndexResult<Vec<JobError>> {
     let channel = Channel::from_url(url)?;
     let items = channel.into_items();
     let mut handles = vec![];
@@ -116,13 +158,15 @@ fn process_feed(
         let ind = Arc::clone(&index);
         let (url, site, title) = match (
             item.link(),
-            Url::parse(&url).unwrap().host_str(),
+            Url::parse(&url)?.host_str(),
             item.title(),
         ) {
             (Some(u), Some(s), Some(t)) => (u.to_string(), s.to_string(), t.to_string()),
             _ => continue,
         };
         let mut scount = 0; //keep track of this site count to update sites_count
+        // Label the article for error reporting before it is moved into the thread.
+        let article_label = url.clone();
         let handle = thread::spawn(move || {
             let sc_cvl = &tc.sites_count;
             let tc_cvl = &tc.total_count;
@@ -179,10 +223,18 @@ fn process_feed(
                 tc_cvar.notify_one();
             }
         });
-        handles.push(handle);
+        handles.push((article_label, handle));
     }
-    for handle in handles {
-        handle.join();
+    // Join the article threads, recording any that panicked so the feed can report them upward
+    // instead of silently dropping the article.
+    let mut errors = vec![];
+    for (label, handle) in handles {
+        if let Result::Err(payload) = handle.join() {
+            errors.push(JobError {
+                url: label,
+                payload: panic_message(payload),
+            });
+        }
     }
-    Result::Ok(())
+    Result::Ok(errors)
 }
@@ -3,38 +3,83 @@ use std::fs::File;
 use std::io::BufReader;
 use std::result::Result;
 
-use std::sync::{Arc, Mutex};
+use std::sync::{mpsc, Arc, Mutex};
 use url::Url;
 
 use crate::common::*;
+use crate::markov::{self, MarkovModel};
 use crate::threadpool::*;
 
 /// Thread pool sizes.
 const SIZE_FEEDS_POOL: usize = 3;
 const SIZE_SITES_POOL: usize = 20;
 
+/// Order of the Markov model built over article titles alongside the index.
+const MARKOV_ORDER: usize = 2;
+
 /// Same as the single/multi threaded version, but using a thread pool. Set up two thread pools:
-/// one for handling feeds, and one for handling articles. Use the sizes above. Push closures
-/// executing `process_feed` into the thread pool.
-pub fn process_feed_file(file_name: &str, index: Arc<Mutex<ArticleIndex>>) -> RssIndexResult<()> {
+/// one for handling feeds, and one for handling articles. Use the sizes above. Each feed builds
+/// its own partial `ArticleIndex` from its article jobs' results, so article jobs take no lock;
+/// the partials are streamed back over a channel and reduced into the final index here. A Markov
+/// model over article titles is built the same way, so callers can generate headlines afterwards.
+/// Feeds that failed are returned alongside the merged result so callers can signal them without
+/// discarding everything that indexed cleanly. An article whose job panics is skipped and is not
+/// included in the returned failures.
+pub fn process_feed_file(
+    file_name: &str,
+) -> RssIndexResult<(ArticleIndex, MarkovModel, Vec<JobError>)> {
     let mut feed_thread_pool = ThreadPool::new(SIZE_FEEDS_POOL);
-    let mut sites_thread_pool = ThreadPool::new(SIZE_SITES_POOL);
+    let sites_thread_pool = ThreadPool::new(SIZE_SITES_POOL);
     let stp_arc = Arc::new(Mutex::new(sites_thread_pool));
     let file = File::open(file_name)?;
     println!("Processing feed file: {}", file_name);
     let channel = Channel::read_from(BufReader::new(file))?;
-    for feed in channel.into_items() {
-        let ind = Arc::clone(&index);
+    let (partials_tx, partials_rx) = mpsc::channel::<(ArticleIndex, MarkovModel)>();
+    let mut handles = vec![];
+    for (position, feed) in channel.into_items().into_iter().enumerate() {
         let stp_arc2 = Arc::clone(&stp_arc);
-        let pff = move || {
+        let tx = partials_tx.clone();
+        let pff = move |ctx: &WorkContext| {
             let url = feed.link().ok_or(RssIndexError::UrlError).unwrap();
-            let title = feed.title().ok_or(RssIndexError::UrlError).unwrap();
-            println!("Processing feed: {} [{}]", title, url);
-            process_feed(url, ind, stp_arc2).unwrap();
+            // Label the worker with the feed url so a panic from here on is attributed to it.
+            ctx.set_name(url.to_string());
+            feed.title().ok_or(RssIndexError::UrlError).unwrap();
+            // Partial index for this feed; only the feed task itself locks it, to fold in the
+            // words its article jobs send back.
+            let local = Arc::new(Mutex::new(ArticleIndex::new()));
+            let model = process_feed(url, Arc::clone(&local), stp_arc2, ctx).unwrap();
+            let local = Arc::try_unwrap(local)
+                .ok()
+                .expect("article jobs outlived their feed")
+                .into_inner()
+                .unwrap();
+            tx.send((local, model)).unwrap();
         };
-        feed_thread_pool.execute(pff);
+        // Feeds nearer the top of the file are indexed first: the earlier the position, the higher
+        // the priority handed to the pool.
+        handles.push(feed_thread_pool.execute_with_priority(u64::MAX - position as u64, pff));
+    }
+    // Drop our own sender so the reduce loop below terminates once every feed has reported.
+    drop(partials_tx);
+    // Drain the feed jobs, reporting how many feeds are still in flight from the pool's live
+    // snapshot, and collecting any that panicked rather than letting them vanish silently.
+    let mut errors: Vec<JobError> = vec![];
+    for handle in handles {
+        println!("\n{} feed(s) still processing...", feed_thread_pool.snapshot().len());
+        if let Result::Err(error) = handle.join() {
+            errors.push(error);
+        }
     }
-    Result::Ok(())
+    // Reduce: fold every feed's partial index and Markov model into the result.
+    let mut index = ArticleIndex::new();
+    let mut model = MarkovModel::new(MARKOV_ORDER);
+    for (partial_index, partial_model) in partials_rx {
+        index.merge(partial_index);
+        model.merge(partial_model);
+    }
+    // Hand the failures back with the merged result: one bad feed shouldn't throw away everything
+    // that indexed cleanly, but the caller still gets to surface them.
+    Result::Ok((index, model, errors))
 }
 
 /// Same as the single/multi threaded version, but using a thread pool. Push closures executing
@@ -43,31 +88,39 @@ fn process_feed(
     url: &str,
     index: Arc<Mutex<ArticleIndex>>,
     sites_pool: Arc<Mutex<ThreadPool>>,
-) -> RssIndexResult<()> {
+    ctx: &WorkContext,
+) -> RssIndexResult<MarkovModel> {
+    ctx.set_status(format!("fetching {}", url));
     let channel = Channel::from_url(url)?;
     let items = channel.into_items();
+    // Each article job returns its indexed words rather than locking a shared index; the feed task
+    // drains the receivers and folds them into its own partial index.
+    let mut receivers = vec![];
     for item in items {
-        let ind = Arc::clone(&index);
         let (url, site, title) = match (
             item.link(),
-            Url::parse(&url).unwrap().host_str(),
+            Url::parse(&url)?.host_str(),
             item.title(),
         ) {
             (Some(u), Some(s), Some(t)) => (u.to_string(), s.to_string(), t.to_string()),
             _ => continue,
         };
-        let pf = move || {
-            println!("Processing article: {} [{}]", title, url);
+        ctx.set_status(format!("indexing {}", title));
+        let job = move || {
             let article = Article::new(url.to_string(), title.to_string());
             let article_words = process_article(&article).unwrap();
-            ind.lock().unwrap().add(
-                site.to_string(),
-                title.to_string(),
-                url.to_string(),
-                article_words,
-            );
+            (site, title, url, article_words)
         };
-        sites_pool.lock().unwrap().execute(pf);
+        receivers.push(sites_pool.lock().unwrap().execute_collect(job));
+    }
+    let mut local = index.lock().unwrap();
+    let mut model = MarkovModel::new(MARKOV_ORDER);
+    for rx in receivers {
+        // A `recv` error means that article's job panicked; skip it rather than abort the feed.
+        if let Result::Ok((site, title, url, article_words)) = rx.recv() {
+            model.feed(&markov::tokenize(&title));
+            local.add(site, title, url, article_words);
+        }
     }
-    Result::Ok(())
+    Result::Ok(model)
 }
@@ -0,0 +1,147 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::result::Result;
+use std::sync::Arc;
+
+use crate::common::{Article, RssIndex, RssIndexResult};
+use crate::threadpool::{ThreadPool, WorkContext};
+
+/// Worker threads answering client connections. A bounded set, so a burst of clients is absorbed
+/// by a fixed number of threads rather than one `thread::spawn` per connection.
+const SIZE_SERVER_POOL: usize = 8;
+
+/// Bind `addr` and answer `GET /search?q=<term>` against `index` until the process is killed. The
+/// index is read-only once built, so it is shared through an `Arc` with no lock on the query path;
+/// each accepted connection is handed to a worker in the pool.
+pub fn serve(addr: &str, index: RssIndex) -> RssIndexResult<()> {
+    let listener = TcpListener::bind(addr)?;
+    println!("Serving search on http://{}/search?q=<term>", addr);
+    let index = Arc::new(index);
+    let mut pool = ThreadPool::new(SIZE_SERVER_POOL);
+    for stream in listener.incoming() {
+        // A single failed accept shouldn't tear down the whole server.
+        let stream = match stream {
+            Result::Ok(stream) => stream,
+            Result::Err(_) => continue,
+        };
+        let index = Arc::clone(&index);
+        pool.execute(move |_ctx: &WorkContext| {
+            if let Result::Err(error) = handle_connection(stream, &index) {
+                eprintln!("connection error: {}", error);
+            }
+        });
+    }
+    Result::Ok(())
+}
+
+/// Read one request line, look the term up, and write a JSON response. Anything that isn't a
+/// `/search` query gets a `400` with an empty result array.
+fn handle_connection(mut stream: TcpStream, index: &RssIndex) -> RssIndexResult<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    match parse_query(&request_line) {
+        Some(term) => write_response(&mut stream, "200 OK", &search_json(index, &term)),
+        None => write_response(&mut stream, "400 Bad Request", "[]"),
+    }
+}
+
+/// Pull the `q` parameter out of a request line like `GET /search?q=rust HTTP/1.1`.
+fn parse_query(request_line: &str) -> Option<String> {
+    let path = request_line.split_whitespace().nth(1)?;
+    let query = path.strip_prefix("/search?")?;
+    for pair in query.split('&') {
+        if let Some(value) = pair.strip_prefix("q=") {
+            return Some(percent_decode(value));
+        }
+    }
+    None
+}
+
+/// Run the term through the same lookup and sort the stdin search loop uses, capped at
+/// `MAX_MATCHES`, and render the hits as a JSON array.
+fn search_json(index: &RssIndex, term: &str) -> String {
+    let lower = term.to_lowercase();
+    let mut articles: Vec<(&Article, &u32)> = match index.index.get(&lower) {
+        Some(matches) => matches.iter().collect(),
+        None => vec![],
+    };
+    // Decreasing hits, then alphabetical title.
+    articles.sort_by(|art1, art2| art2.1.cmp(art1.1).then(art1.0.title.cmp(&art2.0.title)));
+    let items: Vec<String> = articles
+        .iter()
+        .take(crate::MAX_MATCHES)
+        .map(|(article, hits)| {
+            format!(
+                "{{\"title\":\"{}\",\"url\":\"{}\",\"hits\":{}}}",
+                json_escape(&article.title),
+                json_escape(&article.url),
+                hits
+            )
+        })
+        .collect();
+    format!("[{}]", items.join(","))
+}
+
+/// Write a minimal HTTP/1.1 response and close the connection.
+fn write_response(stream: &mut TcpStream, status: &str, body: &str) -> RssIndexResult<()> {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes())?;
+    stream.flush()?;
+    Result::Ok(())
+}
+
+/// Decode the `application/x-www-form-urlencoded` value of `q`: `+` becomes a space and `%XX` an
+/// escaped byte. Malformed escapes are left as-is.
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut out: Vec<u8> = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => match (hex_val(bytes[i + 1]), hex_val(bytes[i + 2])) {
+                (Some(hi), Some(lo)) => {
+                    out.push((hi << 4) | lo);
+                    i += 2;
+                }
+                _ => out.push(b'%'),
+            },
+            byte => out.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Value of a single hex digit, or `None` if it isn't one.
+fn hex_val(byte: u8) -> Option<u8> {
+    match byte {
+        b'0'..=b'9' => Some(byte - b'0'),
+        b'a'..=b'f' => Some(byte - b'a' + 10),
+        b'A'..=b'F' => Some(byte - b'A' + 10),
+        _ => None,
+    }
+}
+
+/// Escape a string for inclusion in a JSON string literal.
+fn json_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
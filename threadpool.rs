@@ -1,67 +1,295 @@
 use crate::common;
-use std::sync::{mpsc, Arc, Mutex};
-use std::thread;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt;
+use std::io::Write;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use std::sync::{mpsc, Arc, Condvar, Mutex};
+use std::thread::{self, ThreadId};
 
-/// Message type to communicate with workers. A JobMsg is either a closure (FnBox annoyance needed
-/// for to get around some limits of Rust closures) or None, which signals the worker to shut down.
-type JobMsg = Option<Box<dyn common::FnBox + Send + 'static>>;
+/// Default priority handed to `execute`. Sits in the middle of the `u64` range so callers can push
+/// work both ahead of and behind the default with `execute_with_priority`.
+const DEFAULT_PRIORITY: u64 = u64::MAX / 2;
 
-/// A ThreadPool should have a sending-end of a mpsc channel (`mpsc::Sender`) and a vector of
-/// `JoinHandle`s for the worker threads.
+/// A boxed job together with the priority it was submitted at. The heap is a max-heap, so jobs are
+/// ordered directly on `priority` and the highest-priority job pops first.
+struct PrioritizedJob {
+    priority: u64,
+    job: Box<dyn common::FnBox + Send + 'static>,
+    result: mpsc::Sender<Result<(), JobError>>,
+}
+
+impl PartialEq for PrioritizedJob {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl Eq for PrioritizedJob {}
+
+impl PartialOrd for PrioritizedJob {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PrioritizedJob {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority.cmp(&other.priority)
+    }
+}
+
+/// What went wrong in a single job. A worker catches the job's panic, downcasts its payload to a
+/// `String`, and pairs it with the feed/article url the job labelled itself with so the submitter
+/// can tell which piece of work failed.
+#[derive(Debug)]
+pub struct JobError {
+    pub url: String,
+    pub payload: String,
+}
+
+impl fmt::Display for JobError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.url.is_empty() {
+            write!(f, "{}", self.payload)
+        } else {
+            write!(f, "{}: {}", self.url, self.payload)
+        }
+    }
+}
+
+/// Returned by `execute`, mirroring `JoinHandle::join`: blocking on it yields `Ok(())` if the job
+/// ran cleanly or a `JobError` if it panicked.
+pub struct JobHandle {
+    receiver: mpsc::Receiver<Result<(), JobError>>,
+}
+
+impl JobHandle {
+    /// Wait for the job to finish and report whether it succeeded.
+    pub fn join(self) -> Result<(), JobError> {
+        self.receiver.recv().unwrap_or_else(|_| {
+            Result::Err(JobError {
+                url: String::new(),
+                payload: "worker disconnected before reporting".to_string(),
+            })
+        })
+    }
+}
+
+/// Progress updates sent from workers to the collector thread, each tagged with the worker's id.
+enum StatusMsg {
+    Name(ThreadId, String),
+    Status(ThreadId, String),
+    Finished(ThreadId),
+}
+
+/// Handed to every running job so it can report what it is doing. A job calls `set_name` once to
+/// label itself and `set_status` as it moves between steps; the pool's collector thread aggregates
+/// these into a live summary line.
+pub struct WorkContext {
+    sender: mpsc::Sender<StatusMsg>,
+    id: ThreadId,
+    /// The feed/article the running job most recently labelled itself with, kept so a panicking
+    /// job can be attributed to a url in its `JobError`.
+    label: Mutex<String>,
+}
+
+impl WorkContext {
+    /// Label the worker (e.g. with the feed it is crawling).
+    pub fn set_name<S: Into<String>>(&self, name: S) {
+        let name = name.into();
+        *self.label.lock().unwrap() = name.clone();
+        let _ = self.sender.send(StatusMsg::Name(self.id, name));
+    }
+
+    /// Report the step the worker is currently on.
+    pub fn set_status<S: Into<String>>(&self, status: S) {
+        let _ = self.sender.send(StatusMsg::Status(self.id, status.into()));
+    }
+
+    /// Report that the worker has no more work for now, clearing it from the summary.
+    pub fn finished(&self) {
+        let _ = self.sender.send(StatusMsg::Finished(self.id));
+    }
+}
+
+/// The work queue shared by the pool and its workers: a priority heap guarded by a mutex, plus a
+/// condvar workers park on while it's empty.
+type Queue = Arc<(Mutex<BinaryHeap<PrioritizedJob>>, Condvar)>;
+
+/// Shared view of every busy worker's `(name, status)`, kept up to date by the collector thread
+/// and read by `ThreadPool::snapshot`.
+type StatusMap = Arc<Mutex<HashMap<ThreadId, (String, String)>>>;
+
+/// A ThreadPool owns the shared job heap, a shutdown flag, the worker `JoinHandle`s, and a
+/// collector thread that renders worker progress.
 pub struct ThreadPool {
-    sender: mpsc::Sender<JobMsg>,
+    queue: Queue,
+    shutdown: Arc<AtomicBool>,
     workers: Vec<thread::JoinHandle<()>>,
+    status_sender: Option<mpsc::Sender<StatusMsg>>,
+    status_map: StatusMap,
+    collector: Option<thread::JoinHandle<()>>,
 }
 
 impl ThreadPool {
-    /// Spin up a thread pool with `num_workers` threads. Workers should all share the same
-    /// receiving end of an mpsc channel (`mpsc::Receiver`) with appropriate synchronization. Each
-    /// thread should loop and (1) listen for new jobs on the channel, (2) execute received jobs,
-    /// and (3) quit the loop if it receives None.
+    /// Spin up a thread pool with `num_workers` threads plus one collector thread. Each worker
+    /// loops and (1) waits on the condvar while the heap is empty and no shutdown has been
+    /// requested, (2) pops the highest priority job and runs it outside the lock, passing in a
+    /// `WorkContext`, and (3) quits once the flag is set and the heap has drained. The collector
+    /// owns the map of worker statuses and repaints a summary line whenever one changes.
     pub fn new(num_workers: usize) -> Self {
-        let (sender, receiver): (mpsc::Sender<JobMsg>, mpsc::Receiver<JobMsg>) = mpsc::channel();
-        let mut workers = vec![];
-        let rx = Arc::new(Mutex::new(receiver));
-        for i in 0..num_workers {
-            let rx_i = Arc::clone(&rx);
-            let worker = thread::spawn(move || loop {
-                //instantiate job here so mutex can unlock before job started
-                let mut job: Box<dyn common::FnBox + std::marker::Send> = Box::new(|| ());
+        let queue: Queue = Arc::new((Mutex::new(BinaryHeap::new()), Condvar::new()));
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let (status_sender, status_receiver) = mpsc::channel::<StatusMsg>();
+        let status_map: StatusMap = Arc::new(Mutex::new(HashMap::new()));
+
+        let collector_map = Arc::clone(&status_map);
+        let collector = thread::spawn(move || {
+            for msg in status_receiver {
                 {
-                    if let Ok(job_msg) = rx_i.lock().unwrap().recv() {
-                        match job_msg {
-                            None => break,
-                            Some(fn_box) => {
-                                job = fn_box;
-                            }
+                    let mut map = collector_map.lock().unwrap();
+                    match msg {
+                        StatusMsg::Name(id, name) => map.entry(id).or_default().0 = name,
+                        StatusMsg::Status(id, status) => map.entry(id).or_default().1 = status,
+                        StatusMsg::Finished(id) => {
+                            map.remove(&id);
                         }
                     }
+                    let summary: Vec<String> = map
+                        .values()
+                        .map(|(name, status)| format!("[{}] {}", name, status))
+                        .collect();
+                    // Repaint a single line: carriage return, contents, clear to end of line.
+                    print!("\r{}\x1b[K", summary.join("  "));
+                }
+                let _ = std::io::stdout().flush();
+            }
+        });
+
+        let mut workers = vec![];
+        for _ in 0..num_workers {
+            let queue = Arc::clone(&queue);
+            let shutdown = Arc::clone(&shutdown);
+            let sender = status_sender.clone();
+            let worker = thread::spawn(move || {
+                let ctx = WorkContext {
+                    sender,
+                    id: thread::current().id(),
+                    label: Mutex::new(String::new()),
+                };
+                let (lock, cvar) = &*queue;
+                loop {
+                    let job = {
+                        let mut heap = lock.lock().unwrap();
+                        while heap.is_empty() && !shutdown.load(AtomicOrdering::Acquire) {
+                            heap = cvar.wait(heap).unwrap();
+                        }
+                        match heap.pop() {
+                            Some(job) => job,
+                            // Nothing left and we've been told to stop.
+                            None => break,
+                        }
+                    };
+                    // Catch a panicking job so one bad feed doesn't take the worker down; report
+                    // the outcome back to whoever holds the `JobHandle`.
+                    let boxed = job.job;
+                    // Clear the previous job's label so a failure is never blamed on it.
+                    ctx.label.lock().unwrap().clear();
+                    let outcome = panic::catch_unwind(AssertUnwindSafe(|| boxed.call_box(&ctx)));
+                    let result = outcome.map_err(|payload| JobError {
+                        url: ctx.label.lock().unwrap().clone(),
+                        payload: panic_message(payload),
+                    });
+                    let _ = job.result.send(result);
+                    ctx.finished();
                 }
-                job.call_box();
             });
             workers.push(worker);
         }
-        ThreadPool { sender, workers }
+        ThreadPool {
+            queue,
+            shutdown,
+            workers,
+            status_sender: Some(status_sender),
+            status_map,
+            collector: Some(collector),
+        }
     }
 
-    /// Push a new job into the thread pool. (You'll probably want to add some constraints.)
-    pub fn execute<F>(&mut self, job: F)
+    /// Push a new job into the pool at the default (middle) priority.
+    pub fn execute<F>(&mut self, job: F) -> JobHandle
     where
         F: common::FnBox + Send + 'static,
     {
-        self.sender.send(Some(Box::new(job))).unwrap();
+        self.execute_with_priority(DEFAULT_PRIORITY, job)
+    }
+
+    /// Push a new job into the pool at an explicit priority. Higher priorities run first.
+    pub fn execute_with_priority<F>(&mut self, priority: u64, job: F) -> JobHandle
+    where
+        F: common::FnBox + Send + 'static,
+    {
+        let (result, receiver) = mpsc::channel();
+        let (lock, cvar) = &*self.queue;
+        lock.lock().unwrap().push(PrioritizedJob {
+            priority,
+            job: Box::new(job),
+            result,
+        });
+        cvar.notify_one();
+        JobHandle { receiver }
+    }
+
+    /// Run a job that returns a value and hand the caller a `Receiver` for that value. The boxed
+    /// closure does the send itself, so the pool stays non-generic while still supporting
+    /// non-`()` workloads. `recv` on the returned channel errors if the job panicked.
+    pub fn execute_collect<F, R>(&mut self, job: F) -> mpsc::Receiver<R>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel();
+        self.execute(move |_ctx: &WorkContext| {
+            let _ = tx.send(job());
+        });
+        rx
+    }
+
+    /// A snapshot of every busy worker's `(name, status)`, so a caller can gauge how much work is
+    /// still outstanding while the pool churns through the queue.
+    pub fn snapshot(&self) -> Vec<(String, String)> {
+        self.status_map.lock().unwrap().values().cloned().collect()
     }
 }
 
 impl Drop for ThreadPool {
-    /// Clean up the thread pool. Send a kill message (None) to each worker, and join each worker.
-    /// This function should only return when all workers have finished.
+    /// Clean up the thread pool. Raise the shutdown flag, wake every parked worker, and join each
+    /// one, then drop our status sender so the collector thread finishes. This function should
+    /// only return when all workers and the collector have finished.
     fn drop(&mut self) {
-        for _ in 0..self.workers.len() {
-            self.sender.send(None).unwrap();
-        }
+        self.shutdown.store(true, AtomicOrdering::Release);
+        self.queue.1.notify_all();
         while let Some(worker) = self.workers.pop() {
-            worker.join();
+            let _ = worker.join();
+        }
+        // Drop the last status sender so the collector's `for msg in receiver` loop ends.
+        self.status_sender.take();
+        if let Some(collector) = self.collector.take() {
+            let _ = collector.join();
         }
     }
 }
+
+/// Best-effort conversion of a panic payload into a readable string. Most panics carry either a
+/// `&str` or a `String`; anything else becomes a generic note.
+pub(crate) fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        (*s).to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}